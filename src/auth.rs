@@ -0,0 +1,144 @@
+use log::{info, warn};
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Default path the approved-device allowlist is persisted to.
+pub const DEFAULT_ALLOWLIST_PATH: &str = "presenterd_allowlist.txt";
+
+/// Outcome of authorizing an incoming payload from a device address.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Decision {
+    /// The device is on the allowlist; its payload should be handled.
+    Authorized,
+    /// The payload completed first-connect pairing; it must not be executed.
+    Paired,
+    /// The device is mid-pairing and its payload was not the expected code.
+    AwaitingCode,
+}
+
+/// Tracks approved device addresses and drives first-connect pairing.
+///
+/// A device that is already on the allowlist is trusted. An unknown device is
+/// shown a short numeric code on the host; it is only added once it echoes that
+/// code back as a command payload (little-endian `u16`).
+pub struct Authorizer {
+    allowed: HashSet<String>,
+    pending: HashMap<String, u16>,
+    path: PathBuf,
+}
+
+impl Authorizer {
+    /// Load the allowlist from `path`, starting empty if the file is absent.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let allowed = std::fs::read_to_string(&path)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Authorizer { allowed, pending: HashMap::new(), path }
+    }
+
+    /// Authorize `payload` from `address`, advancing the pairing state machine.
+    pub fn authorize(&mut self, address: &str, payload: &[u8]) -> Decision {
+        if self.allowed.contains(address) {
+            return Decision::Authorized;
+        }
+
+        match self.pending.get(address).copied() {
+            Some(expected) => {
+                if payload_code(payload) == Some(expected) {
+                    self.pending.remove(address);
+                    self.allowed.insert(address.to_string());
+                    self.persist();
+                    info!("Device {address} paired and added to allowlist");
+                    Decision::Paired
+                } else {
+                    warn!("Incorrect pairing code from {address}; ignoring payload");
+                    Decision::AwaitingCode
+                }
+            }
+            None => {
+                let code = pairing_code();
+                self.pending.insert(address.to_string(), code);
+                println!("Pairing request from {address}. Enter code on remote: {code:04}");
+                info!("Awaiting pairing code {code:04} from {address}");
+                Decision::AwaitingCode
+            }
+        }
+    }
+
+    /// Write the current allowlist back to disk, logging on failure.
+    fn persist(&self) {
+        let body = self
+            .allowed
+            .iter()
+            .cloned()
+            .collect::<Vec<_>>()
+            .join("\n");
+        if let Err(err) = std::fs::write(&self.path, body) {
+            warn!("Failed to persist allowlist to {}: {err}", self.path.display());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> Authorizer {
+        let path = std::env::temp_dir().join(format!("presenterd_test_{name}"));
+        let _ = std::fs::remove_file(&path);
+        Authorizer::load(path)
+    }
+
+    #[test]
+    fn payload_code_decodes_little_endian() {
+        assert_eq!(payload_code(&[0x39, 0x05]), Some(0x0539));
+        assert_eq!(payload_code(&[0x01]), None);
+        assert_eq!(payload_code(&[0x01, 0x02, 0x03]), None);
+    }
+
+    #[test]
+    fn pairing_state_machine() {
+        let mut auth = scratch("pairing");
+        let addr = "AA:BB:CC:DD:EE:FF";
+
+        // First contact from an unknown device starts pairing.
+        assert_eq!(auth.authorize(addr, &[0x01]), Decision::AwaitingCode);
+        let code = *auth.pending.get(addr).expect("code pending");
+
+        // A wrong code does not pair the device.
+        let wrong = code.wrapping_add(1);
+        assert_eq!(auth.authorize(addr, &wrong.to_le_bytes()), Decision::AwaitingCode);
+        assert!(!auth.allowed.contains(addr));
+
+        // The correct code completes pairing and the payload is not executed.
+        assert_eq!(auth.authorize(addr, &code.to_le_bytes()), Decision::Paired);
+        assert!(auth.allowed.contains(addr));
+
+        // Subsequent commands from the now-trusted device are authorized.
+        assert_eq!(auth.authorize(addr, &[0x01]), Decision::Authorized);
+    }
+}
+
+/// Decode a pairing code carried as a little-endian `u16` payload.
+fn payload_code(payload: &[u8]) -> Option<u16> {
+    match payload {
+        [lo, hi] => Some(u16::from_le_bytes([*lo, *hi])),
+        _ => None,
+    }
+}
+
+/// Generate a fresh, unpredictable 4-digit pairing code. Because the code is
+/// random and only shown host-side, a remote cannot derive it from its own
+/// address — it must read the code off the host to pair.
+fn pairing_code() -> u16 {
+    rand::thread_rng().gen_range(0u16..10000)
+}
@@ -1,3 +1,60 @@
+use tokio::sync::mpsc::Receiver;
+use uuid::Uuid;
+
+/// Boxed callback invoked for each received payload, tagged with the
+/// originating device address (empty when the backend cannot supply one).
+pub type OnData = Box<dyn Fn(&str, &[u8]) + Send + 'static>;
+
+/// Uniform error surfaced by every backend, hiding platform-specific types.
+#[derive(Debug)]
+pub enum BleError {
+    /// The underlying platform stack reported a failure.
+    Backend(String),
+    /// The requested operation is not supported on this platform.
+    Unsupported(&'static str),
+}
+
+impl std::fmt::Display for BleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BleError::Backend(msg) => write!(f, "BLE backend error: {msg}"),
+            BleError::Unsupported(what) => write!(f, "unsupported on this platform: {what}"),
+        }
+    }
+}
+
+impl std::error::Error for BleError {}
+
+/// Common interface every platform backend implements, so `main.rs` can drive
+/// the server without `cfg`-specific signatures leaking into the call site.
+///
+/// Backends are always used as a concrete type, so the implicit auto-trait
+/// bounds on the returned futures are sufficient here.
+#[allow(async_fn_in_trait)]
+pub trait BleServer {
+    /// Begin advertising and serving the GATT application, returning only once
+    /// the server stops or errors.
+    async fn start(
+        &self,
+        service_uuid: Uuid,
+        characteristic_uuid: Uuid,
+        name: &str,
+        on_data_received: OnData,
+        notifier: Receiver<Vec<u8>>,
+    ) -> Result<(), BleError>;
+
+    /// Request the server to shut down.
+    async fn stop(&self) -> Result<(), BleError>;
+}
+
+/// The backend selected for the current target OS.
+#[cfg(target_os = "linux")]
+pub use platform::LinuxBleServer as PlatformServer;
+#[cfg(target_os = "windows")]
+pub use platform::WindowsBleServer as PlatformServer;
+#[cfg(target_os = "macos")]
+pub use platform::MacosBleServer as PlatformServer;
+
 #[cfg(target_os = "windows")]
 pub mod platform {
    
@@ -5,8 +62,15 @@ pub mod platform {
         service_uuid: uuid::Uuid,
         characteristic_uuid: uuid::Uuid,
         name: &str,
-        on_data_received: impl Fn(&[u8]) + Send + 'static
+        on_data_received: impl Fn(&str, &[u8]) + Send + 'static,
+        // Outbound notifications are only plumbed through on the Linux backend for
+        // now; the C shim has no GATT notify bridge, so the receiver is drained
+        // into a background task and dropped.
+        notifier: tokio::sync::mpsc::Receiver<Vec<u8>>,
     ) {
+        let mut notifier = notifier;
+        tokio::spawn(async move { while notifier.recv().await.is_some() {} });
+
         let name = CString::new(name).unwrap();
         let service = CString::new(service_uuid.to_string()).unwrap();
         let charac = CString::new(characteristic_uuid.to_string()).unwrap();
@@ -18,6 +82,29 @@ pub mod platform {
         unsafe { internal_stop_ble_server(); }
     }
 
+    /// Windows backend bridging to the `ble_server` C shim.
+    #[derive(Default)]
+    pub struct WindowsBleServer;
+
+    impl super::BleServer for WindowsBleServer {
+        async fn start(
+            &self,
+            service_uuid: uuid::Uuid,
+            characteristic_uuid: uuid::Uuid,
+            name: &str,
+            on_data_received: super::OnData,
+            notifier: tokio::sync::mpsc::Receiver<Vec<u8>>,
+        ) -> Result<(), super::BleError> {
+            run_ble_server(service_uuid, characteristic_uuid, name, on_data_received, notifier).await;
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<(), super::BleError> {
+            stop_ble_server().await;
+            Ok(())
+        }
+    }
+
     use std::ffi::CString;
     use std::os::raw::{c_char, c_uchar};
 
@@ -37,11 +124,11 @@ pub mod platform {
     use std::sync::Mutex;
     use std::sync::OnceLock;
 
-    static CALLBACK: OnceLock<Mutex<Option<Box<dyn Fn(&[u8]) + Send + 'static>>>> = OnceLock::new();
+    static CALLBACK: OnceLock<Mutex<Option<Box<dyn Fn(&str, &[u8]) + Send + 'static>>>> = OnceLock::new();
 
     fn callback_stub<F>(callback: F) -> Callback
     where
-        F: Fn(&[u8]) + Send + 'static,
+        F: Fn(&str, &[u8]) + Send + 'static,
     {
         CALLBACK.get_or_init(|| Mutex::new(None)).lock().unwrap().replace(Box::new(callback));
 
@@ -53,7 +140,9 @@ pub mod platform {
             if let Some(mutex) = CALLBACK.get() {
                 if let Ok(mut guard) = mutex.lock() {
                     if let Some(callback) = guard.as_mut() {
-                        callback(data);
+                        // The C shim does not yet surface the originating device
+                        // address, so authorization cannot be enforced here.
+                        callback("", data);
                     }
                 }
             }
@@ -68,7 +157,7 @@ pub mod platform {
     use bluer::{
         adv::Advertisement,
         gatt::{
-            CharacteristicReader,
+            CharacteristicReader, CharacteristicWriter,
             local::{
                 Application, Characteristic, CharacteristicControlEvent, CharacteristicNotify,
                 CharacteristicNotifyMethod, CharacteristicWrite, CharacteristicWriteMethod,
@@ -77,15 +166,27 @@ pub mod platform {
         },
     };
     use futures::{StreamExt, future, pin_mut};
-    use log::{debug, error, info, trace};
+    use log::{debug, error, info, trace, warn};
+    use std::sync::OnceLock;
     use std::time::Duration;
-    use tokio::io::AsyncReadExt;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::sync::Notify;
+
+    /// Signalled by [`stop_ble_server`] to break the otherwise-indefinite
+    /// advertising loop. Everything short of an explicit stop is treated as
+    /// transient so a client can reconnect mid-presentation.
+    static SHUTDOWN: OnceLock<Notify> = OnceLock::new();
+
+    fn shutdown_signal() -> &'static Notify {
+        SHUTDOWN.get_or_init(Notify::new)
+    }
 
     pub async fn run_ble_server(
         service_uuid: uuid::Uuid,
         characteristic_uuid: uuid::Uuid,
         name: &str,
-        on_data_received: impl Fn(&[u8]) + Send + 'static,
+        on_data_received: impl Fn(&str, &[u8]) + Send + 'static,
+        mut notifier: tokio::sync::mpsc::Receiver<Vec<u8>>,
     ) -> bluer::Result<()> {
         let session = bluer::Session::new().await?;
         let adapter = session.default_adapter().await?;
@@ -101,7 +202,7 @@ pub mod platform {
             adapter.address().await?
         );
 
-        let le_advertisement = Advertisement {
+        let make_advertisement = || Advertisement {
             service_uuids: vec![service_uuid].into_iter().collect(),
             discoverable: Some(true),
             local_name: Some(name.to_string()),
@@ -110,7 +211,9 @@ pub mod platform {
             ..Default::default()
         };
 
-        let _ = adapter.advertise(le_advertisement).await?;
+        // Keep the advertisement handle alive for the lifetime of the server so
+        // the watchdog can re-assert it if the adapter drops.
+        let mut adv_handle = Some(adapter.advertise(make_advertisement()).await?);
 
         trace!(
             "Serving GATT service on Bluetooth adapter {}",
@@ -145,25 +248,76 @@ pub mod platform {
             ..Default::default()
         };
 
-        let _ = adapter.serve_gatt_application(app).await?;
+        // Keep the GATT application registered for the lifetime of the server.
+        let _app_handle = adapter.serve_gatt_application(app).await?;
 
         info!("Service ready. Press enter to quit.");
 
         let mut read_buf = Vec::new();
         let mut reader_opt: Option<CharacteristicReader> = None;
+        let mut writer_opt: Option<CharacteristicWriter> = None;
+        let mut addr_opt: Option<String> = None;
+        let mut notifier_open = true;
+
+        // Periodically make sure the adapter is still powered and advertising.
+        let mut watchdog = tokio::time::interval(Duration::from_secs(5));
+        watchdog.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         pin_mut!(char_control);
 
         loop {
             tokio::select! {
+                _ = shutdown_signal().notified() => {
+                    info!("Shutdown requested; stopping advertising loop");
+                    break;
+                }
                 evt = char_control.next() => {
                     match evt {
                         Some(CharacteristicControlEvent::Write(req)) => {
                             trace!("Accepting write event with MTU {} from {}", req.mtu(), req.device_address());
                             read_buf = vec![0; req.mtu()];
+                            addr_opt = Some(req.device_address().to_string());
                             reader_opt = Some(req.accept()?);
                         },
-                        _ => break,
+                        Some(CharacteristicControlEvent::Notify(writer)) => {
+                            trace!("Accepting notify session with MTU {}", writer.mtu());
+                            writer_opt = Some(writer);
+                        },
+                        // A disconnect or any other non-write control event is
+                        // transient: reset the per-connection state and keep
+                        // serving so a phone can reconnect without restarting.
+                        Some(_) => {
+                            trace!("Transient control event; resetting connection state");
+                            reader_opt = None;
+                            writer_opt = None;
+                            addr_opt = None;
+                            read_buf = Vec::new();
+                        }
+                        // The control stream itself ended (e.g. the application
+                        // handle was dropped). There is nothing left to serve,
+                        // so stop instead of hot-spinning on an always-ready
+                        // `None`.
+                        None => {
+                            info!("Characteristic control stream ended; stopping");
+                            break;
+                        }
+                    }
+                }
+                _ = watchdog.tick() => {
+                    match adapter.is_powered().await {
+                        Ok(true) => {}
+                        Ok(false) | Err(_) => {
+                            warn!("Adapter not powered; re-asserting and re-advertising");
+                            if let Err(err) = adapter.set_powered(true).await {
+                                error!("Failed to re-power adapter: {err}");
+                                continue;
+                            }
+                            adv_handle = None;
+                            match adapter.advertise(make_advertisement()).await {
+                                Ok(handle) => adv_handle = Some(handle),
+                                Err(err) => error!("Failed to re-advertise: {err}"),
+                            }
+                        }
                     }
                 }
                 read_res = async {
@@ -179,8 +333,9 @@ pub mod platform {
                         }
                         Ok(n) => {
                             let value = read_buf[0..n].to_vec();
-                            trace!("Write request with {} bytes: {:x?}", n, &value);
-                            on_data_received(&value);
+                            let address = addr_opt.as_deref().unwrap_or("");
+                            trace!("Write request from {} with {} bytes: {:x?}", address, n, &value);
+                            on_data_received(address, &value);
                         }
                         Err(err) => {
                             error!("Error reading from stream: {err}");
@@ -188,13 +343,164 @@ pub mod platform {
                         }
                     }
                 }
+                notify = async {
+                    match notifier_open {
+                        true => notifier.recv().await,
+                        false => future::pending().await,
+                    }
+                } => {
+                    match notify {
+                        Some(buf) => {
+                            if let Some(writer) = &mut writer_opt {
+                                trace!("Sending {} notification bytes: {:x?}", buf.len(), &buf);
+                                if let Err(err) = writer.write_all(&buf).await {
+                                    error!("Error writing notification: {err}");
+                                    writer_opt = None;
+                                }
+                            } else {
+                                trace!("Dropping notification, no subscriber: {:x?}", &buf);
+                            }
+                        }
+                        // The outbound channel closed; stop polling it but keep
+                        // the server alive until an explicit stop.
+                        None => notifier_open = false,
+                    }
+                }
             }
         }
         Ok(())
     }
 
     pub async fn stop_ble_server() {
-        // No specific action needed for Linux, as the server will stop when the application exits.
-        info!("Stopping BLE server on Linux is handled by exiting the application.");
+        info!("Stopping BLE server on Linux");
+        // notify_one stores a permit if no waiter is registered yet, avoiding a
+        // race with the select! loop re-arming its shutdown branch.
+        shutdown_signal().notify_one();
+    }
+
+    /// Linux backend backed by BlueZ through `bluer`.
+    #[derive(Default)]
+    pub struct LinuxBleServer;
+
+    impl super::BleServer for LinuxBleServer {
+        async fn start(
+            &self,
+            service_uuid: uuid::Uuid,
+            characteristic_uuid: uuid::Uuid,
+            name: &str,
+            on_data_received: super::OnData,
+            notifier: tokio::sync::mpsc::Receiver<Vec<u8>>,
+        ) -> Result<(), super::BleError> {
+            run_ble_server(service_uuid, characteristic_uuid, name, on_data_received, notifier)
+                .await
+                .map_err(|err| super::BleError::Backend(err.to_string()))
+        }
+
+        async fn stop(&self) -> Result<(), super::BleError> {
+            stop_ble_server().await;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+pub mod platform {
+    use log::{debug, info, trace};
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_uchar};
+    use std::sync::Mutex;
+    use std::sync::OnceLock;
+
+    pub async fn run_ble_server(
+        service_uuid: uuid::Uuid,
+        characteristic_uuid: uuid::Uuid,
+        name: &str,
+        on_data_received: impl Fn(&str, &[u8]) + Send + 'static,
+        notifier: tokio::sync::mpsc::Receiver<Vec<u8>>,
+    ) {
+        let mut notifier = notifier;
+        tokio::spawn(async move { while notifier.recv().await.is_some() {} });
+
+        debug!("UUIDs for this application:");
+        debug!("  Service UUID: {service_uuid}");
+        debug!("  Characteristic UUID: {characteristic_uuid}");
+        trace!("Starting CoreBluetooth peripheral {name}");
+
+        let name = CString::new(name).unwrap();
+        let service = CString::new(service_uuid.to_string()).unwrap();
+        let charac = CString::new(characteristic_uuid.to_string()).unwrap();
+
+        unsafe {
+            internal_start_ble_server(
+                name.as_ptr(),
+                service.as_ptr(),
+                charac.as_ptr(),
+                callback_stub(on_data_received),
+            );
+        }
+    }
+
+    pub async fn stop_ble_server() {
+        info!("Stopping CoreBluetooth peripheral");
+        unsafe { internal_stop_ble_server(); }
+    }
+
+    type Callback = unsafe extern "C" fn(*const c_uchar, usize);
+
+    #[link(name = "ble_server")]
+    unsafe extern "C" {
+        fn internal_start_ble_server(
+            name: *const c_char,
+            service_uuid: *const c_char,
+            characteristic_uuid: *const c_char,
+            callback: Callback,
+        );
+        fn internal_stop_ble_server();
+    }
+
+    static CALLBACK: OnceLock<Mutex<Option<Box<dyn Fn(&str, &[u8]) + Send + 'static>>>> = OnceLock::new();
+
+    fn callback_stub<F>(callback: F) -> Callback
+    where
+        F: Fn(&str, &[u8]) + Send + 'static,
+    {
+        CALLBACK.get_or_init(|| Mutex::new(None)).lock().unwrap().replace(Box::new(callback));
+
+        unsafe extern "C" fn wrapper(data: *const c_uchar, len: usize) {
+            let data = unsafe { std::slice::from_raw_parts(data, len) };
+            if let Some(mutex) = CALLBACK.get() {
+                if let Ok(mut guard) = mutex.lock() {
+                    if let Some(callback) = guard.as_mut() {
+                        // The CoreBluetooth shim does not yet surface the central's
+                        // identifier, so authorization cannot be enforced here.
+                        callback("", data);
+                    }
+                }
+            }
+        }
+        wrapper
+    }
+
+    /// macOS backend bridging to the CoreBluetooth `ble_server` shim.
+    #[derive(Default)]
+    pub struct MacosBleServer;
+
+    impl super::BleServer for MacosBleServer {
+        async fn start(
+            &self,
+            service_uuid: uuid::Uuid,
+            characteristic_uuid: uuid::Uuid,
+            name: &str,
+            on_data_received: super::OnData,
+            notifier: tokio::sync::mpsc::Receiver<Vec<u8>>,
+        ) -> Result<(), super::BleError> {
+            run_ble_server(service_uuid, characteristic_uuid, name, on_data_received, notifier).await;
+            Ok(())
+        }
+
+        async fn stop(&self) -> Result<(), super::BleError> {
+            stop_ble_server().await;
+            Ok(())
+        }
     }
 }
@@ -0,0 +1,251 @@
+use enigo::{Direction, Enigo, Key, Keyboard};
+use log::{error, info, warn};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Default path searched for a user keymap at startup.
+pub const DEFAULT_CONFIG_PATH: &str = "presenterd.toml";
+
+/// A single action bound to an incoming command byte.
+#[derive(Debug, Clone)]
+pub enum Command {
+    /// Tap a single key (press then release).
+    Key(Key),
+    /// Tap a key while one or more modifiers are held, e.g. Alt+Tab.
+    Chord { modifiers: Vec<Key>, key: Key },
+    /// Type a literal string.
+    Text(String),
+    /// Run several commands back to back.
+    Sequence(Vec<Command>),
+}
+
+impl Command {
+    /// Execute the command against the shared `Enigo` instance.
+    fn execute(&self, enigo: &mut Enigo) {
+        match self {
+            Command::Key(key) => {
+                let _ = enigo.key(*key, Direction::Press);
+                let _ = enigo.key(*key, Direction::Release);
+            }
+            Command::Chord { modifiers, key } => {
+                for modifier in modifiers {
+                    let _ = enigo.key(*modifier, Direction::Press);
+                }
+                let _ = enigo.key(*key, Direction::Press);
+                let _ = enigo.key(*key, Direction::Release);
+                for modifier in modifiers.iter().rev() {
+                    let _ = enigo.key(*modifier, Direction::Release);
+                }
+            }
+            Command::Text(text) => {
+                let _ = enigo.text(text);
+            }
+            Command::Sequence(commands) => {
+                for command in commands {
+                    command.execute(enigo);
+                }
+            }
+        }
+    }
+}
+
+/// Dispatch table mapping command bytes to [`Command`]s.
+pub struct KeyMap {
+    map: HashMap<u8, Command>,
+}
+
+impl KeyMap {
+    /// The built-in binding used when no config file is present: the original
+    /// two-button `0x01 => RightArrow`, `0x02 => LeftArrow` behavior.
+    pub fn builtin_default() -> Self {
+        let mut map = HashMap::new();
+        map.insert(0x01, Command::Key(Key::RightArrow));
+        map.insert(0x02, Command::Key(Key::LeftArrow));
+        KeyMap { map }
+    }
+
+    /// Load the keymap from `path`, falling back to [`KeyMap::builtin_default`]
+    /// when the file is absent or cannot be parsed.
+    pub fn load<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref();
+        match std::fs::read_to_string(path) {
+            Ok(contents) => match Self::from_toml(&contents) {
+                Ok(keymap) => {
+                    info!("Loaded keymap from {}", path.display());
+                    keymap
+                }
+                Err(err) => {
+                    error!("Failed to parse {}: {err}; using built-in keymap", path.display());
+                    Self::builtin_default()
+                }
+            },
+            Err(_) => {
+                info!("No keymap at {}; using built-in keymap", path.display());
+                Self::builtin_default()
+            }
+        }
+    }
+
+    /// Parse a TOML document into a keymap.
+    fn from_toml(contents: &str) -> Result<Self, String> {
+        let raw: RawConfig = toml::from_str(contents).map_err(|e| e.to_string())?;
+        let mut map = HashMap::new();
+        for (byte, command) in raw.commands {
+            let byte = parse_byte(&byte)?;
+            map.insert(byte, command.into_command()?);
+        }
+        Ok(KeyMap { map })
+    }
+
+    /// Execute the command bound to `byte`, returning whether one was found.
+    pub fn dispatch(&self, byte: u8, enigo: &mut Enigo) -> bool {
+        match self.map.get(&byte) {
+            Some(command) => {
+                command.execute(enigo);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// Serde view of the TOML config, converted into a [`KeyMap`] after parsing.
+#[derive(Deserialize)]
+struct RawConfig {
+    commands: HashMap<String, RawCommand>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RawCommand {
+    // `Chord` must precede `Key`: untagged deserialization tries variants in
+    // order, so the more specific `{ modifiers, key }` shape is matched before
+    // it could collapse into a plain `{ key }`.
+    Chord {
+        modifiers: Vec<String>,
+        key: String,
+    },
+    Key { key: String },
+    Text { text: String },
+    Sequence { sequence: Vec<RawCommand> },
+}
+
+impl RawCommand {
+    fn into_command(self) -> Result<Command, String> {
+        match self {
+            RawCommand::Key { key } => Ok(Command::Key(parse_key(&key)?)),
+            RawCommand::Chord { modifiers, key } => {
+                let modifiers = modifiers
+                    .iter()
+                    .map(|m| parse_key(m))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Command::Chord { modifiers, key: parse_key(&key)? })
+            }
+            RawCommand::Text { text } => Ok(Command::Text(text)),
+            RawCommand::Sequence { sequence } => {
+                let commands = sequence
+                    .into_iter()
+                    .map(RawCommand::into_command)
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(Command::Sequence(commands))
+            }
+        }
+    }
+}
+
+/// Parse a config table key (`"5"`, `"0x10"`) into a command byte.
+fn parse_byte(raw: &str) -> Result<u8, String> {
+    let raw = raw.trim();
+    let parsed = if let Some(hex) = raw.strip_prefix("0x").or_else(|| raw.strip_prefix("0X")) {
+        u8::from_str_radix(hex, 16)
+    } else {
+        raw.parse::<u8>()
+    };
+    parsed.map_err(|_| format!("invalid command byte: {raw}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chord_preserves_modifiers() {
+        let toml = r#"
+            [commands]
+            3 = { modifiers = ["Alt"], key = "Tab" }
+        "#;
+        let keymap = KeyMap::from_toml(toml).expect("parses");
+        match keymap.map.get(&3) {
+            Some(Command::Chord { modifiers, key }) => {
+                assert_eq!(modifiers.len(), 1);
+                assert!(matches!(modifiers[0], Key::Alt));
+                assert!(matches!(key, Key::Tab));
+            }
+            other => panic!("expected chord, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn plain_key_parses() {
+        let toml = r#"
+            [commands]
+            1 = { key = "RightArrow" }
+        "#;
+        let keymap = KeyMap::from_toml(toml).expect("parses");
+        assert!(matches!(keymap.map.get(&1), Some(Command::Key(Key::RightArrow))));
+    }
+
+    #[test]
+    fn parse_byte_handles_decimal_and_hex() {
+        assert_eq!(parse_byte("16"), Ok(16));
+        assert_eq!(parse_byte("0x10"), Ok(16));
+        assert!(parse_byte("notabyte").is_err());
+    }
+
+    #[test]
+    fn parse_key_maps_single_char_to_unicode() {
+        assert!(matches!(parse_key("b"), Ok(Key::Unicode('b'))));
+        assert!(matches!(parse_key("F5"), Ok(Key::F5)));
+        assert!(parse_key("NotAKey").is_err());
+    }
+}
+
+/// Parse a key name into an [`enigo::Key`]. Single characters map to
+/// [`Key::Unicode`]; everything else matches a known named key.
+fn parse_key(name: &str) -> Result<Key, String> {
+    let key = match name {
+        "RightArrow" => Key::RightArrow,
+        "LeftArrow" => Key::LeftArrow,
+        "UpArrow" => Key::UpArrow,
+        "DownArrow" => Key::DownArrow,
+        "PageUp" => Key::PageUp,
+        "PageDown" => Key::PageDown,
+        "Home" => Key::Home,
+        "End" => Key::End,
+        "Escape" | "Esc" => Key::Escape,
+        "Return" | "Enter" => Key::Return,
+        "Space" => Key::Space,
+        "Tab" => Key::Tab,
+        "F1" => Key::F1,
+        "F5" => Key::F5,
+        "VolumeUp" => Key::VolumeUp,
+        "VolumeDown" => Key::VolumeDown,
+        "VolumeMute" => Key::VolumeMute,
+        "Alt" => Key::Alt,
+        "Control" | "Ctrl" => Key::Control,
+        "Shift" => Key::Shift,
+        "Meta" | "Super" | "Cmd" => Key::Meta,
+        other => {
+            let mut chars = other.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Unicode(c),
+                _ => {
+                    warn!("Unknown key name: {other}");
+                    return Err(format!("unknown key: {other}"));
+                }
+            }
+        }
+    };
+    Ok(key)
+}
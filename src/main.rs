@@ -1,9 +1,37 @@
-use enigo::{Enigo, Key, Keyboard, Settings};
+use enigo::{Button, Coordinate, Enigo, Mouse, Settings};
 use log::{error, info};
 use tokio::{time::sleep, io::{AsyncBufReadExt, BufReader}};
 use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU32, Ordering};
 
+mod auth;
 mod ble_server;
+mod keymap;
+
+use auth::{Authorizer, Decision};
+use ble_server::BleServer;
+use keymap::KeyMap;
+
+// Opcodes split by direction: `NOTIFY_*` are outbound (host → remote, over the
+// GATT notification channel); `POINTER_*` are inbound (remote → host, over the
+// write characteristic). The two spaces are independent, but are kept
+// numerically distinct to avoid confusion when reading traces.
+
+/// Outbound notification byte acknowledging a dispatched keymap command or
+/// pointer click. It is sent once the input has been issued to enigo; enigo's
+/// own error results are not checked.
+const NOTIFY_ACK: u8 = 0x06;
+/// Outbound opcode prefixing a periodic host status payload.
+const NOTIFY_STATUS: u8 = 0x20;
+
+/// Relative-pointer opcode: `0x10` followed by two little-endian `i16` values
+/// (`dx`, `dy`) applied as a relative mouse delta. Motion packets are not
+/// acknowledged so a gyroscope can stream them at high frequency.
+const POINTER_MOVE: u8 = 0x10;
+/// Pointer opcode: press+release the left mouse button.
+const POINTER_LEFT_CLICK: u8 = 0x11;
+/// Pointer opcode: press+release the right mouse button.
+const POINTER_RIGHT_CLICK: u8 = 0x12;
 
 /// UUIDs for the GATT service
 const SERVICE_UUID: uuid::Uuid = uuid::Uuid::from_u128(0x1234567812345678123456789abcdef0);
@@ -17,44 +45,106 @@ const NAME: &str = "Presenter Remote";
 /// This function interprets the command and executes the corresponding action
 ///
 /// value: &[u8] is expected to contain the command byte(s).
+///
+/// `notifier` carries the GATT notification back-channel: an ACK byte is pushed
+/// back to the remote after each bound keymap command or pointer click is
+/// issued (whether enigo reported an error is not checked), and `commands` tracks
+/// the running count of dispatched keybind commands surfaced by the periodic
+/// status notification.
 #[inline(always)]
-fn handle_command(value: &[u8], enigo: &mut Enigo) {
-    let command = value.first().unwrap_or(&0x00);
-    let command = match command {
-        0x01 => Key::RightArrow,
-        0x02 => Key::LeftArrow,
-        _ => {
-            error!("Unknown command received: {:x?}", value);
+fn handle_command(
+    value: &[u8],
+    enigo: &mut Enigo,
+    keymap: &KeyMap,
+    notifier: &tokio::sync::mpsc::Sender<Vec<u8>>,
+    commands: &AtomicU32,
+) {
+    let command = *value.first().unwrap_or(&0x00);
+    match command {
+        POINTER_MOVE => {
+            if value.len() < 5 {
+                error!("Malformed pointer-move packet: {:x?}", value);
+                return;
+            }
+            let dx = i16::from_le_bytes([value[1], value[2]]) as i32;
+            let dy = i16::from_le_bytes([value[3], value[4]]) as i32;
+            let _ = enigo.move_mouse(dx, dy, Coordinate::Rel);
+            // Motion is fire-and-forget: no ACK, no advance count.
             return;
         }
-    };
-    enigo.key(command, enigo::Direction::Press).expect("");
-    enigo.key(command, enigo::Direction::Release).expect("");
+        POINTER_LEFT_CLICK => {
+            let _ = enigo.button(Button::Left, enigo::Direction::Click);
+        }
+        POINTER_RIGHT_CLICK => {
+            let _ = enigo.button(Button::Right, enigo::Direction::Click);
+        }
+        _ => {
+            if !keymap.dispatch(command, enigo) {
+                error!("Unknown command received: {:x?}", value);
+                return;
+            }
+            commands.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    if let Err(err) = notifier.try_send(vec![NOTIFY_ACK]) {
+        error!("Failed to queue acknowledgement notification: {err}");
+    }
 }
 
 
 #[tokio::main(flavor = "multi_thread", worker_threads = 1)]
-async fn main() -> bluer::Result<()> {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     env_logger::init();
 
     let enigo = Arc::new(Mutex::new(Enigo::new(&Settings::default()).unwrap()));
 
     let enigo_clone = enigo.clone();
 
-    let ble_task = tokio::spawn(async move {
-        ble_server::platform::run_ble_server(
-            SERVICE_UUID,
-            CHARACTERISTIC_UUID,
-            NAME,
-            move |value| {
-                if let Ok(mut enigo) = enigo_clone.lock() {
-                    handle_command(value, &mut *enigo)
-                } else {
-                    error!("Failed to lock Enigo mutex");
-                }
+    let commands = Arc::new(AtomicU32::new(0));
+    let (notifier_tx, notifier_rx) = tokio::sync::mpsc::channel::<Vec<u8>>(64);
+
+    // Periodically push the current dispatched-command count to the remote.
+    let status_tx = notifier_tx.clone();
+    let status_commands = commands.clone();
+    tokio::spawn(async move {
+        loop {
+            sleep(std::time::Duration::from_secs(5)).await;
+            let count = status_commands.load(Ordering::Relaxed);
+            let mut payload = vec![NOTIFY_STATUS];
+            payload.extend_from_slice(&count.to_le_bytes());
+            if status_tx.send(payload).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let keymap = KeyMap::load(keymap::DEFAULT_CONFIG_PATH);
+    let authorizer = Arc::new(Mutex::new(Authorizer::load(auth::DEFAULT_ALLOWLIST_PATH)));
+
+    let cmd_tx = notifier_tx.clone();
+    let cmd_commands = commands.clone();
+    let on_data: ble_server::OnData = Box::new(move |address: &str, value: &[u8]| {
+        match authorizer.lock() {
+            Ok(mut authorizer) => match authorizer.authorize(address, value) {
+                Decision::Authorized => {}
+                Decision::Paired | Decision::AwaitingCode => return,
             },
-        )
-        .await
+            Err(_) => {
+                error!("Failed to lock authorizer");
+                return;
+            }
+        }
+        if let Ok(mut enigo) = enigo_clone.lock() {
+            handle_command(value, &mut *enigo, &keymap, &cmd_tx, &cmd_commands)
+        } else {
+            error!("Failed to lock Enigo mutex");
+        }
+    });
+
+    let ble_task = tokio::spawn(async move {
+        ble_server::PlatformServer
+            .start(SERVICE_UUID, CHARACTERISTIC_UUID, NAME, on_data, notifier_rx)
+            .await
     });
 
     let stdin = BufReader::new(tokio::io::stdin());
@@ -65,8 +155,9 @@ async fn main() -> bluer::Result<()> {
     tokio::select! {
         res = ble_task => {
             match res {
-                Ok(_) => info!("BLE server exited successfully"),
-                Err(e) => error!("BLE server error: {}", e),
+                Ok(Ok(())) => info!("BLE server exited successfully"),
+                Ok(Err(e)) => error!("BLE server error: {}", e),
+                Err(e) => error!("BLE task join error: {}", e),
             }
         }
         _ = lines.next_line() => {
@@ -75,8 +166,8 @@ async fn main() -> bluer::Result<()> {
     }
 
     info!("Cleaning up BLE server...");
-    
-    ble_server::platform::stop_ble_server().await;
+
+    ble_server::PlatformServer.stop().await?;
 
     sleep(std::time::Duration::from_millis(100)).await;
     Ok(())